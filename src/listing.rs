@@ -0,0 +1,58 @@
+use crate::manifest::Manifest;
+use miette::{Context, IntoDiagnostic, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{fs::File, io, path::Path};
+
+/// A VPM/UPM repository listing entry: the package manifest plus the
+/// fields a VRChat Creator Companion repo index needs to locate and
+/// verify the artifact.
+#[derive(Serialize)]
+struct Listing<'a> {
+    name: &'a str,
+    version: &'a str,
+    #[serde(flatten)]
+    rest: &'a serde_json::Value,
+    url: &'a str,
+    #[serde(rename = "zipSHA256")]
+    zip_sha256: String,
+}
+
+/// Hashes the package at `package_path` and writes a listing entry for
+/// it, built from `manifest` and `url`, to `listing_path`.
+pub fn write(
+    manifest: &Manifest,
+    url: &str,
+    package_path: &Path,
+    listing_path: &Path,
+) -> Result<()> {
+    let zip_sha256 = hash_file(package_path).wrap_err("Failed to hash package")?;
+
+    let listing = Listing {
+        name: manifest.name,
+        version: manifest.version,
+        rest: &manifest.rest,
+        url,
+        zip_sha256,
+    };
+
+    let file = File::create(listing_path)
+        .into_diagnostic()
+        .wrap_err("Failed to create listing file")?;
+    serde_json::to_writer_pretty(file, &listing)
+        .into_diagnostic()
+        .wrap_err("Failed to write listing file")?;
+
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)
+        .into_diagnostic()
+        .wrap_err("Failed to open package")?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .into_diagnostic()
+        .wrap_err("Failed to read package")?;
+    Ok(hex::encode(hasher.finalize()))
+}
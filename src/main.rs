@@ -1,3 +1,4 @@
+mod listing;
 mod manifest;
 mod unity_package;
 mod upm;
@@ -10,22 +11,103 @@ use std::{
     io::{prelude::*, BufReader},
     path::PathBuf,
 };
-use tracing::info_span;
-use unity_package::PackageEntry;
+use tracing::{info_span, warn};
+use unity_package::{Package, PackageEntries, PackageEntry};
 use upm::PackageBuilder;
+use zip::{write::FileOptions, CompressionMethod};
+
+#[derive(Clone, Copy, clap::ArgEnum)]
+enum Compression {
+    Deflate,
+    Zstd,
+    Store,
+}
+
+impl From<Compression> for CompressionMethod {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::Deflate => CompressionMethod::Deflated,
+            Compression::Zstd => CompressionMethod::Zstd,
+            Compression::Store => CompressionMethod::Stored,
+        }
+    }
+}
 
 #[derive(Parser)]
 #[clap(author, version, about)]
 struct CliArgs {
-    /// The path to the .unitypackage file.
-    #[clap(parse(from_os_str), value_name = "UNITY_PACKAGE")]
-    package: PathBuf,
+    /// The path to the .unitypackage file, or an http(s) URL to fetch it
+    /// from.
+    #[clap(value_name = "UNITY_PACKAGE")]
+    package: String,
     /// The path to the package.json describing the UPM package.
     #[clap(parse(from_os_str), value_name = "PACKAGE_JSON")]
     vpm_json: PathBuf,
     /// The path to write the converted package.
     #[clap(parse(from_os_str), value_name = "UPM_PACKAGE")]
     vpm: PathBuf,
+    /// The URL the converted package will be hosted at, recorded in the
+    /// VPM repository listing entry written alongside UPM_PACKAGE.
+    #[clap(long, value_name = "URL")]
+    url: String,
+    /// The compression method to use for package entries. Note that
+    /// Zstd-compressed entries can't be read by Unity/VCC's .NET zip
+    /// reader, which only understands Deflate and Store; only use it for
+    /// packages that won't be opened by Unity tooling.
+    #[clap(long, arg_enum, default_value = "deflate")]
+    compression: Compression,
+    /// The compression level to use, in the range accepted by
+    /// --compression's algorithm. Defaults to that algorithm's default.
+    #[clap(long, value_name = "N")]
+    compression_level: Option<i32>,
+    /// The Unix timestamp to record as every entry's modification time,
+    /// so that converting the same inputs twice produces a byte-for-byte
+    /// identical package. Defaults to SOURCE_DATE_EPOCH, or 1980-01-01 if
+    /// that isn't set either, since zip timestamps can't represent
+    /// anything before 1980.
+    #[clap(long, value_name = "UNIX_TIMESTAMP")]
+    mtime: Option<i64>,
+}
+
+/// The earliest Unix timestamp a zip entry's modification time can
+/// represent, used as the default `--mtime` since the Unix epoch itself
+/// is out of range.
+const ZIP_EPOCH: i64 = 315_532_800;
+
+/// Converts a Unix timestamp into the `zip::DateTime` recorded in every
+/// entry's header.
+fn mtime_date_time(unix_timestamp: i64) -> Result<zip::DateTime> {
+    let date_time = time::OffsetDateTime::from_unix_timestamp(unix_timestamp)
+        .into_diagnostic()
+        .wrap_err("Invalid --mtime timestamp")?;
+    zip::DateTime::try_from(date_time)
+        .into_diagnostic()
+        .wrap_err("--mtime is outside the range a zip timestamp can represent")
+}
+
+/// Converts each entry yielded by `entries`, writing it into `vpm`.
+/// Returns `true` if one or more entries could not be processed.
+fn convert_entries<R>(entries: PackageEntries<R>, vpm: &mut PackageBuilder<File>) -> Result<bool>
+where
+    R: Read,
+{
+    let mut failed = false;
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                eprintln!("{}", error);
+                failed = true;
+                continue;
+            }
+        };
+
+        let PackageEntry { path, mut content } = entry;
+        vpm.append(&path, &mut content)
+            .wrap_err_with(|| format!("Failed to process {path:?}"))?;
+    }
+
+    Ok(failed)
 }
 
 fn main() -> Result<()> {
@@ -35,12 +117,6 @@ fn main() -> Result<()> {
 
     let _ = info_span!("Converting package {package}", package = ?args.package).enter();
 
-    let package = File::open(&args.package)
-        .into_diagnostic()
-        .wrap_err("Failed to open Unity package")?;
-    let mut package = unity_package::Package::new(package);
-    let package_entries = package.entries().wrap_err("Failed to read Unity package")?;
-
     let mut manifest_string = String::new();
     {
         let manifest = File::open(&args.vpm_json)
@@ -56,34 +132,75 @@ fn main() -> Result<()> {
     let manifest = Manifest::parse(&manifest_string, &args.vpm_json.to_string_lossy())
         .wrap_err("Failed to parse manifest")?;
 
+    let mtime = match args.mtime {
+        Some(mtime) => mtime,
+        None => std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.parse().ok())
+            .unwrap_or(ZIP_EPOCH),
+    };
+
+    if matches!(args.compression, Compression::Zstd) {
+        warn!("--compression zstd produces entries Unity/VCC's .NET zip reader can't open; only use it for packages that won't go through Unity tooling.");
+    }
+
+    let mut options = FileOptions::default()
+        .compression_method(args.compression.into())
+        .last_modified_time(mtime_date_time(mtime)?)
+        .unix_permissions(0o644);
+    if let Some(compression_level) = args.compression_level {
+        options = options.compression_level(Some(compression_level));
+    }
+    upm::validate_options(options).wrap_err("Invalid compression options")?;
+
     let mut vpm = {
         let vpm = File::create(&args.vpm)
             .into_diagnostic()
             .wrap_err("Failed to create VPM package")?;
-        PackageBuilder::new(vpm, format!("{}@{}", manifest.name, manifest.version))
+        PackageBuilder::new(
+            vpm,
+            format!("{}@{}", manifest.name, manifest.version),
+            options,
+        )
+        .wrap_err("Failed to create VPM package")?
     };
 
     vpm.append("package.json", &mut manifest_string.as_bytes())
         .wrap_err("Failed to write package.json")?;
 
-    let mut failed = false;
-    for entry in package_entries {
-        let entry = match entry {
-            Ok(entry) => entry,
-            Err(error) => {
-                eprintln!("{}", error);
-                failed = true;
-                continue;
-            }
-        };
-
-        let PackageEntry { path, mut content } = entry;
-        vpm.append(&path, &mut content)
-            .wrap_err_with(|| format!("Failed to process {path:?}"))?;
-    }
+    let failed = match url::Url::parse(&args.package) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+            let response = ureq::get(url.as_str())
+                .call()
+                .into_diagnostic()
+                .wrap_err("Failed to fetch Unity package")?;
+            let mut package = Package::new(response.into_reader());
+            let package_entries = package.entries().wrap_err("Failed to read Unity package")?;
+            convert_entries(package_entries, &mut vpm)?
+        }
+        _ => {
+            let file = File::open(&args.package)
+                .into_diagnostic()
+                .wrap_err("Failed to open Unity package")?;
+            let mut package =
+                Package::new_seekable(file).wrap_err("Failed to scan Unity package")?;
+            let package_entries = package.entries().wrap_err("Failed to read Unity package")?;
+            convert_entries(package_entries, &mut vpm)?
+        }
+    };
 
     vpm.finish().wrap_err("Failed to close VPM file")?;
 
+    let mut listing_path = args.vpm.clone().into_os_string();
+    listing_path.push(".listing.json");
+    listing::write(
+        &manifest,
+        &args.url,
+        &args.vpm,
+        &PathBuf::from(listing_path),
+    )
+    .wrap_err("Failed to write VPM repository listing entry")?;
+
     if failed {
         bail!("One or more entries could not be proccessed.");
     }
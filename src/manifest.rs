@@ -7,6 +7,21 @@ use thiserror::Error;
 pub struct Manifest<'a> {
     pub name: &'a str,
     pub version: &'a str,
+    /// Every other field of package.json (`displayName`, `unity`,
+    /// `vpmDependencies`, etc.), kept around untouched so it can be
+    /// reproduced in full when building a listing entry.
+    #[serde(flatten)]
+    pub rest: serde_json::Value,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+pub enum ManifestError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Json(SerdeError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Invalid(ValidationError),
 }
 
 #[derive(Error, Debug, Diagnostic)]
@@ -24,15 +39,154 @@ impl fmt::Display for SerdeError {
     }
 }
 
+#[derive(Error, Debug, Diagnostic)]
+#[error("{message}")]
+pub struct ValidationError {
+    message: String,
+    #[source_code]
+    src: NamedSource,
+    #[label("Around here")]
+    bad_bit: SourceSpan,
+}
+
 impl<'a> Manifest<'a> {
-    pub fn parse<'b>(content: &'a str, file_name: &'b str) -> Result<Manifest<'a>, SerdeError> {
-        serde_json::from_str(content).map_err(|source| SerdeError {
-            src: NamedSource::new(file_name, content.to_owned()),
-            bad_bit: SourceSpan::new(
-                SourceOffset::from_location(&content, source.line(), source.column()),
-                SourceOffset::from(1),
-            ),
-            serde: source,
-        })
+    pub fn parse<'b>(content: &'a str, file_name: &'b str) -> Result<Manifest<'a>, ManifestError> {
+        let manifest: Manifest = serde_json::from_str(content).map_err(|source| {
+            ManifestError::Json(SerdeError {
+                src: NamedSource::new(file_name, content.to_owned()),
+                bad_bit: SourceSpan::new(
+                    SourceOffset::from_location(content, source.line(), source.column()),
+                    SourceOffset::from(1),
+                ),
+                serde: source,
+            })
+        })?;
+
+        if let Err(message) = validate_name(manifest.name) {
+            return Err(ManifestError::Invalid(invalid_field(
+                content, file_name, "name", message,
+            )));
+        }
+
+        if let Err(error) = semver::Version::parse(manifest.version) {
+            return Err(ManifestError::Invalid(invalid_field(
+                content,
+                file_name,
+                "version",
+                format!(
+                    "{version:?} is not a valid semantic version: {error}",
+                    version = manifest.version
+                ),
+            )));
+        }
+
+        Ok(manifest)
+    }
+}
+
+/// Checks that `name` is a well-formed reverse-DNS package id: lowercase,
+/// dot-separated segments of letters, digits, hyphens, and underscores,
+/// with no characters that would be hostile to the `name@version` zip
+/// prefix it becomes.
+fn validate_name(name: &str) -> Result<(), String> {
+    let segments: Vec<&str> = name.split('.').collect();
+    if segments.len() < 2 {
+        return Err(format!(
+            "{name:?} is not a reverse-DNS package id (it needs at least two dot-separated segments)"
+        ));
+    }
+
+    for segment in segments {
+        if segment.is_empty()
+            || !segment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "{name:?} is not a valid package id (segment {segment:?} must contain only lowercase letters, digits, hyphens, and underscores)"
+            ));
+        }
     }
+
+    Ok(())
+}
+
+/// Builds a [`ValidationError`] whose label points at `key`'s value in
+/// `content`, falling back to spanning the whole file if the key can't
+/// be found (it's already been deserialized successfully, so this should
+/// only happen for unusual formatting).
+fn invalid_field(content: &str, file_name: &str, key: &str, message: String) -> ValidationError {
+    let (offset, len) = locate_value(content, key).unwrap_or((0, content.len()));
+    ValidationError {
+        message,
+        src: NamedSource::new(file_name, content.to_owned()),
+        bad_bit: SourceSpan::new(SourceOffset::from(offset), SourceOffset::from(len)),
+    }
+}
+
+/// Finds the byte offset and length of `key`'s quoted string value among
+/// the *top-level* members of a JSON object, ignoring any same-named key
+/// nested inside an object or array value (e.g. inside
+/// `vpmDependencies`).
+fn locate_value(content: &str, key: &str) -> Option<(usize, usize)> {
+    let needle = format!("\"{key}\"");
+    let mut depth = 0u32;
+    let mut expect_key = false;
+    let mut chars = content.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                if depth == 1 && expect_key && content[i..].starts_with(&needle) {
+                    if let Some(span) = value_span_after(content, i + needle.len()) {
+                        return Some(span);
+                    }
+                }
+                skip_string(&mut chars);
+                expect_key = false;
+            }
+            '{' => {
+                depth += 1;
+                expect_key = depth == 1;
+            }
+            ',' => expect_key = depth == 1,
+            '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Advances `chars` past the remainder of a JSON string whose opening
+/// quote has already been consumed, accounting for `\"` escapes.
+fn skip_string(chars: &mut std::str::CharIndices<'_>) {
+    let mut escaped = false;
+    for (_, c) in chars.by_ref() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            break;
+        }
+    }
+}
+
+/// Given the byte offset right after a key's closing quote, finds the
+/// span of its value if that value is a quoted string.
+fn value_span_after(content: &str, after_key: usize) -> Option<(usize, usize)> {
+    let colon_offset = content[after_key..].find(':')?;
+    let after_colon = after_key + colon_offset + 1;
+
+    let value_offset = content[after_colon..].find(|c: char| !c.is_whitespace())?;
+    let value_start = after_colon + value_offset;
+
+    if content.as_bytes().get(value_start) != Some(&b'"') {
+        return None;
+    }
+
+    let closing_offset = content[value_start + 1..].find('"')?;
+    Some((value_start, closing_offset + 2))
 }
@@ -13,6 +13,21 @@ use tracing::{debug_span, warn};
 
 const MAX_ASSET_MEM: usize = 32 * 1024 * 1024;
 
+/// Splits a tar entry path into the Unity asset GUID (its first
+/// component) and the name of the part within it (`asset`,
+/// `asset.meta`, `pathname`, etc.), as long as the path is exactly two
+/// components long.
+fn split_id_part(entry_path: &std::path::Path) -> Option<(OsString, String)> {
+    let mut components = entry_path.components();
+    match (components.next(), components.next(), components.next()) {
+        (Some(id), Some(part), None) => Some((
+            id.as_os_str().to_owned(),
+            part.as_os_str().to_string_lossy().into_owned(),
+        )),
+        _ => None,
+    }
+}
+
 #[derive(Default)]
 struct AssetParts {
     asset: Option<SpooledTempFile>,
@@ -51,6 +66,88 @@ where
     }
 }
 
+impl<F> Package<GzDecoder<F>>
+where
+    F: Read + Seek,
+{
+    /// Builds a package from a seekable reader by making two passes over
+    /// the archive: the first reads only the (tiny) `pathname` entries to
+    /// resolve every asset's real path up front, then the reader is
+    /// rewound and the second pass streams each `asset`/`asset.meta` body
+    /// straight through, since its path is already known. This avoids
+    /// buffering assets into a temporary file while waiting for a
+    /// `pathname` entry that, in a typical `.unitypackage`, comes after
+    /// the asset it names. Parts of an asset whose GUID has no `pathname`
+    /// entry are still buffered during the second pass and are never
+    /// yielded.
+    pub fn new_seekable(mut reader: F) -> Result<Self> {
+        let paths = Self::scan_paths(&mut reader).wrap_err("Failed to scan asset paths")?;
+
+        reader
+            .seek(SeekFrom::Start(0))
+            .into_diagnostic()
+            .wrap_err("Failed to rewind package")?;
+
+        Ok(Self {
+            tar: Archive::new(GzDecoder::new(reader)),
+            paths: paths
+                .into_iter()
+                .map(|(id, path)| (id, ItemStatus::KnownPath(path)))
+                .collect(),
+        })
+    }
+
+    fn scan_paths(reader: &mut F) -> Result<HashMap<OsString, String>> {
+        let mut tar = Archive::new(GzDecoder::new(reader));
+        let mut paths = HashMap::new();
+
+        for entry in tar
+            .entries()
+            .into_diagnostic()
+            .wrap_err("Failed to read tar header")?
+        {
+            let mut entry = entry
+                .into_diagnostic()
+                .wrap_err("Failed to read entry header")?;
+
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let entry_path = entry
+                .path()
+                .into_diagnostic()
+                .wrap_err("Failed to read package entry path")?
+                .into_owned();
+
+            let (id, part) = match split_id_part(&entry_path) {
+                Some(parts) => parts,
+                None => continue,
+            };
+
+            if part != "pathname" {
+                continue;
+            }
+
+            let mut name = String::new();
+            entry
+                .read_to_string(&mut name)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to read asset name from {entry_path:?}"))?;
+
+            if name.starts_with("Assets/") {
+                name.drain(.."Assets/".len());
+            } else {
+                warn!("Ignoring non-asset path {name:?}");
+            }
+
+            paths.insert(id, name);
+        }
+
+        Ok(paths)
+    }
+}
+
 impl<R> Package<R>
 where
     R: Read,
@@ -169,13 +266,9 @@ where
 
             let _ = debug_span!("Inspecting entry {entry}", entry = ?entry_path).enter();
 
-            let mut components = entry_path.components();
-            let (id, part) = match (components.next(), components.next(), components.next()) {
-                (Some(id), Some(part), None) => (
-                    id.as_os_str().to_owned(),
-                    part.as_os_str().to_string_lossy(),
-                ),
-                _ => {
+            let (id, part) = match split_id_part(&entry_path) {
+                Some(parts) => parts,
+                None => {
                     warn!("Skipping entry because it is not expected.");
                     continue;
                 }
@@ -1,42 +1,93 @@
 use miette::{Context, IntoDiagnostic, Result};
-use std::io::{self, prelude::*};
+use std::{
+    fs::File,
+    io::{self, prelude::*, Cursor, SeekFrom},
+};
 use zip::{write::FileOptions, ZipWriter};
 
+/// Writes a single throwaway entry into an in-memory zip with `options`
+/// and immediately discards it, so an unsupported compression method or
+/// level is reported right away instead of after an entire package has
+/// been processed.
+pub fn validate_options(options: FileOptions) -> Result<()> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("probe", options)
+        .into_diagnostic()
+        .wrap_err("Failed to create zip header")?;
+    zip.finish()
+        .into_diagnostic()
+        .wrap_err("Failed to finish zip archive")?;
+    Ok(())
+}
+
 pub struct PackageBuilder<F>
 where
     F: Write + Seek,
 {
     zip: ZipWriter<F>,
     prefix: String,
+    options: FileOptions,
+    /// Every entry's content, copied into one shared cache file as it's
+    /// appended rather than a temp file per entry, so `finish` can flush
+    /// every entry in a stable, sorted order (which makes the resulting
+    /// archive reproducible) without holding one open file handle per
+    /// entry for the whole run.
+    cache: File,
+    cache_len: u64,
+    entries: Vec<(String, u64, u64)>,
 }
 
 impl<F> PackageBuilder<F>
 where
     F: Write + Seek,
 {
-    pub fn new(writer: F, prefix: String) -> Self {
-        Self {
+    pub fn new(writer: F, prefix: String, options: FileOptions) -> Result<Self> {
+        let cache = tempfile::tempfile()
+            .into_diagnostic()
+            .wrap_err("Failed to create entry cache")?;
+        Ok(Self {
             zip: ZipWriter::new(writer),
             prefix,
-        }
+            options,
+            cache,
+            cache_len: 0,
+            entries: Vec::new(),
+        })
     }
 
     pub fn append<R>(&mut self, path: &str, reader: &mut R) -> Result<()>
     where
         R: Read,
     {
-        self.zip
-            .start_file(format!("{}/{}", self.prefix, path), FileOptions::default())
-            .into_diagnostic()
-            .wrap_err("Failed to create zip header")?;
-        io::copy(reader, &mut self.zip)
+        let offset = self.cache_len;
+        let size = io::copy(reader, &mut self.cache)
             .into_diagnostic()
-            .wrap_err("Failed to write data")?;
+            .wrap_err("Failed to cache entry")?;
+        self.cache_len += size;
+
+        self.entries.push((path.to_owned(), offset, size));
 
         Ok(())
     }
 
     pub fn finish(mut self) -> Result<()> {
+        self.entries.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+        for (path, offset, size) in self.entries {
+            self.cache
+                .seek(SeekFrom::Start(offset))
+                .into_diagnostic()
+                .wrap_err("Failed to seek entry cache")?;
+
+            self.zip
+                .start_file(format!("{}/{}", self.prefix, path), self.options)
+                .into_diagnostic()
+                .wrap_err("Failed to create zip header")?;
+            io::copy(&mut (&self.cache).take(size), &mut self.zip)
+                .into_diagnostic()
+                .wrap_err("Failed to write data")?;
+        }
+
         self.zip
             .finish()
             .into_diagnostic()